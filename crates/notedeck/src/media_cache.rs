@@ -0,0 +1,195 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Content-addressed store for the actual downloaded media bytes behind a
+/// [`crate::urls::UrlMimes`] entry. Chunks are hashed with blake3 and land in
+/// `<base>/chunks/<hex-hash>`; a per-URL manifest lists the ordered hashes
+/// plus the MIME type.
+pub struct MediaCache {
+    base_dir: PathBuf,
+    /// Reference counts per chunk hash, used to decide what's safe to evict.
+    refcounts: HashMap<String, u32>,
+    byte_budget: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub mime_type: String,
+    pub chunks: Vec<String>,
+}
+
+/// Content-defined chunking parameters; chunk size is clamped to
+/// `[MIN_CHUNK_SIZE, MAX_CHUNK_SIZE]`.
+const MIN_CHUNK_SIZE: usize = 2 * 1024;
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+const WINDOW_SIZE: usize = 64;
+/// A boundary is emitted when the low bits of the rolling hash are zero.
+/// 13 bits gives an expected chunk size of ~8KiB.
+const BOUNDARY_MASK: u64 = (1 << 13) - 1;
+
+impl MediaCache {
+    pub fn new(base_dir: PathBuf, byte_budget: u64) -> Self {
+        let _ = fs::create_dir_all(base_dir.join("chunks"));
+        let _ = fs::create_dir_all(base_dir.join("manifests"));
+
+        let mut cache = Self {
+            base_dir,
+            refcounts: HashMap::new(),
+            byte_budget,
+        };
+        cache.load_refcounts();
+        cache
+    }
+
+    /// Rebuild `refcounts` from every manifest already on disk, so a chunk
+    /// from a prior session isn't mistaken for unreferenced and evicted.
+    fn load_refcounts(&mut self) {
+        let Ok(entries) = fs::read_dir(self.base_dir.join("manifests")) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let Ok(bytes) = fs::read(entry.path()) else {
+                continue;
+            };
+            let Ok(manifest) = bincode::deserialize::<Manifest>(&bytes) else {
+                continue;
+            };
+
+            for hash in manifest.chunks {
+                *self.refcounts.entry(hash).or_insert(0) += 1;
+            }
+        }
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.base_dir.join("chunks").join(hash)
+    }
+
+    fn manifest_path(&self, url: &str) -> PathBuf {
+        self.base_dir
+            .join("manifests")
+            .join(blake3::hash(url.as_bytes()).to_hex().to_string())
+    }
+
+    /// Split, hash, and store a freshly downloaded body under `url`, only
+    /// writing chunks that aren't already present on disk.
+    pub fn ingest(&mut self, url: &str, mime_type: &str, body: &[u8]) -> Result<(), Error> {
+        let chunks = chunk_content(body);
+        let mut hashes = Vec::with_capacity(chunks.len());
+
+        for chunk in chunks {
+            let hash = blake3::hash(chunk).to_hex().to_string();
+            self.merge_known_chunks(&hash, chunk)?;
+            hashes.push(hash);
+        }
+
+        let manifest = Manifest {
+            mime_type: mime_type.to_owned(),
+            chunks: hashes,
+        };
+        let encoded = bincode::serialize(&manifest).map_err(|e| Error::Generic(e.to_string()))?;
+        fs::write(self.manifest_path(url), encoded)?;
+
+        Ok(())
+    }
+
+    /// Write `chunk` under `hash` if it isn't already stored, and bump its
+    /// reference count either way.
+    fn merge_known_chunks(&mut self, hash: &str, chunk: &[u8]) -> Result<(), Error> {
+        *self.refcounts.entry(hash.to_owned()).or_insert(0) += 1;
+
+        let path = self.chunk_path(hash);
+        if path.exists() {
+            return Ok(());
+        }
+
+        fs::write(path, chunk)?;
+        Ok(())
+    }
+
+    /// Reconstruct the original bytes for `url` from its manifest's chunks.
+    pub fn get(&self, url: &str) -> Option<(String, Vec<u8>)> {
+        let manifest_bytes = fs::read(self.manifest_path(url)).ok()?;
+        let manifest: Manifest = bincode::deserialize(&manifest_bytes).ok()?;
+
+        let mut body = Vec::new();
+        for hash in &manifest.chunks {
+            body.extend_from_slice(&fs::read(self.chunk_path(hash)).ok()?);
+        }
+
+        Some((manifest.mime_type, body))
+    }
+
+    /// Evict least-referenced chunks until the store is back under `byte_budget`.
+    pub fn evict(&mut self) -> Result<(), Error> {
+        let chunks_dir = self.base_dir.join("chunks");
+        let mut entries: Vec<(String, u64)> = Vec::new();
+        let mut total_bytes: u64 = 0;
+
+        for entry in fs::read_dir(&chunks_dir)? {
+            let entry = entry?;
+            let size = entry.metadata()?.len();
+            total_bytes += size;
+
+            if let Some(name) = entry.file_name().to_str() {
+                entries.push((name.to_owned(), size));
+            }
+        }
+
+        if total_bytes <= self.byte_budget {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|(hash, _)| self.refcounts.get(hash).copied().unwrap_or(0));
+
+        for (hash, size) in entries {
+            if total_bytes <= self.byte_budget {
+                break;
+            }
+
+            fs::remove_file(chunks_dir.join(&hash))?;
+            self.refcounts.remove(&hash);
+            total_bytes -= size;
+        }
+
+        Ok(())
+    }
+}
+
+/// Split `data` into content-defined chunks via a rolling hash over a
+/// sliding window, clamped to `[MIN_CHUNK_SIZE, MAX_CHUNK_SIZE]`.
+fn chunk_content(data: &[u8]) -> Vec<&[u8]> {
+    if data.is_empty() {
+        return vec![];
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+
+    for i in 0..data.len() {
+        hash = hash.wrapping_shl(1).wrapping_add(data[i] as u64);
+
+        let len = i - start + 1;
+        if len < MIN_CHUNK_SIZE {
+            continue;
+        }
+
+        let at_boundary = i + 1 >= start + WINDOW_SIZE && (hash & BOUNDARY_MASK) == 0;
+        if at_boundary || len >= MAX_CHUNK_SIZE {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+
+    chunks
+}
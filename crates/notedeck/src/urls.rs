@@ -4,18 +4,41 @@ use std::{
     io::{Read, Write},
     path::PathBuf,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
 use egui::TextBuffer;
 use poll_promise::Promise;
+use serde::{Deserialize, Serialize};
 
+use crate::media_cache::MediaCache;
 use crate::Error;
 
 const FILE_NAME: &str = "urls.bin";
 const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// How often [`UrlMimes::handle_io`] runs a [`MediaCache::evict`] pass.
+const EVICT_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
-type UrlsToMime = HashMap<String, String>;
+/// Marks a framed cache file, as opposed to the old headerless `bincode` blob.
+const MAGIC: u8 = 0xDC;
+/// Bumped when the framed payload's shape changes. An unrecognized version is
+/// treated as unreadable rather than guessed at.
+const VERSION: u8 = 2;
+
+/// How long a resolved MIME type is trusted before it's refetched.
+const MIME_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Doubled on each consecutive `HttpFailure`, capped at [`MAX_BACKOFF`].
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedMime {
+    mime_type: String,
+    fetched_at: SystemTime,
+}
+
+type UrlsToMime = HashMap<String, CachedMime>;
 
 /// caches mime type for a URL. saves to disk on interval [`SAVE_INTERVAL`]
 pub struct UrlCache {
@@ -40,12 +63,22 @@ impl UrlCache {
     }
 
     pub fn get_type(&self, url: &str) -> Option<String> {
+        Some(self.get_entry(url)?.mime_type)
+    }
+
+    fn get_entry(&self, url: &str) -> Option<CachedMime> {
         self.cache.read().ok()?.get(url).cloned()
     }
 
     pub fn set_type(&mut self, url: String, mime_type: String) {
         if let Ok(mut locked_cache) = self.cache.write() {
-            locked_cache.insert(url, mime_type);
+            locked_cache.insert(
+                url,
+                CachedMime {
+                    mime_type,
+                    fetched_at: SystemTime::now(),
+                },
+            );
         }
     }
 
@@ -85,9 +118,31 @@ fn read_from_disk(path: PathBuf) -> Promise<Option<UrlsToMime>> {
             let mut file = File::open(path)?;
             let mut buffer = Vec::new();
             file.read_to_end(&mut buffer)?;
-            let data: UrlsToMime =
-                bincode::deserialize(&buffer).map_err(|e| Error::Generic(e.to_string()))?;
-            Ok(data)
+
+            if buffer.first() != Some(&MAGIC) {
+                // pre-framing headerless blob of bare mime types, migrated on
+                // next save. Its fetch times are unknown, so entries start out
+                // expired and get refetched by `UrlMimes::get`.
+                let legacy: HashMap<String, String> =
+                    bincode::deserialize(&buffer).map_err(|e| Error::Generic(e.to_string()))?;
+                Ok(legacy
+                    .into_iter()
+                    .map(|(url, mime_type)| {
+                        let fetched_at = SystemTime::UNIX_EPOCH;
+                        (url, CachedMime { mime_type, fetched_at })
+                    })
+                    .collect())
+            } else if buffer.get(1) != Some(&VERSION) {
+                // unrecognized version: don't guess at its shape, just fail
+                Err(Error::Generic(format!(
+                    "unsupported UrlCache format version {:?}",
+                    buffer.get(1)
+                )))
+            } else {
+                let decompressed = zstd::stream::decode_all(&buffer[2..])
+                    .map_err(|e| Error::Generic(e.to_string()))?;
+                bincode::deserialize(&decompressed).map_err(|e| Error::Generic(e.to_string()))
+            }
         })();
 
         match result {
@@ -105,20 +160,29 @@ fn read_from_disk(path: PathBuf) -> Promise<Option<UrlsToMime>> {
 fn save_to_disk(path: PathBuf, cache: Arc<RwLock<UrlsToMime>>) {
     std::thread::spawn(move || {
         let result: Result<(), Error> = (|| {
-            if let Ok(cache) = cache.read() {
-                let cache = &*cache;
-                let encoded =
-                    bincode::serialize(cache).map_err(|e| Error::Generic(e.to_string()))?;
-                let mut file = File::create(&path)?;
-                file.write_all(&encoded)?;
-                file.sync_all()?;
-                tracing::info!("Saved UrlCache to disk.");
-                Ok(())
-            } else {
-                Err(Error::Generic(
-                    "Could not read UrlCache behind RwLock".to_owned(),
-                ))
-            }
+            let cache = cache
+                .read()
+                .map_err(|_| Error::Generic("Could not read UrlCache behind RwLock".to_owned()))?;
+            let encoded = bincode::serialize(&*cache).map_err(|e| Error::Generic(e.to_string()))?;
+            let compressed =
+                zstd::stream::encode_all(&encoded[..], 0).map_err(|e| Error::Generic(e.to_string()))?;
+            drop(cache);
+
+            // write to a sibling temp file and rename over the target so a
+            // reader never observes a partially-written cache
+            let mut tmp_path = path.clone().into_os_string();
+            tmp_path.push(".tmp");
+            let tmp_path = PathBuf::from(tmp_path);
+
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&[MAGIC, VERSION])?;
+            file.write_all(&compressed)?;
+            file.sync_all()?;
+            drop(file);
+
+            std::fs::rename(&tmp_path, &path)?;
+            tracing::info!("Saved UrlCache to disk.");
+            Ok(())
         })();
 
         if let Err(e) = result {
@@ -150,6 +214,22 @@ fn ehttp_get_mime_type(url: &str, sender: poll_promise::Sender<MimeResult>) {
     );
 }
 
+fn ehttp_get_body(url: &str, sender: poll_promise::Sender<BodyResult>) {
+    let request = ehttp::Request::get(url);
+
+    let url = url.to_owned();
+    ehttp::fetch(
+        request,
+        move |response: Result<ehttp::Response, String>| match response {
+            Ok(resp) => sender.send(Ok(resp.bytes)),
+            Err(err) => {
+                sender.send(Err(HttpError::HttpFailure));
+                tracing::error!("failed ehttp body fetch for {url}: {err}");
+            }
+        },
+    );
+}
+
 #[derive(Debug)]
 enum HttpError {
     HttpFailure,
@@ -157,6 +237,7 @@ enum HttpError {
 }
 
 type MimeResult = Result<String, HttpError>;
+type BodyResult = Result<Vec<u8>, HttpError>;
 
 fn extract_mime_type(content_type: &str) -> &str {
     content_type
@@ -166,38 +247,90 @@ fn extract_mime_type(content_type: &str) -> &str {
         .trim()
 }
 
+/// In-memory backoff state for a URL that's recently failed to resolve.
+struct FailureState {
+    next_attempt_at: Instant,
+    backoff: Duration,
+}
+
 pub struct UrlMimes {
     pub cache: UrlCache,
+    /// Downloaded media bytes behind a resolved MIME type. See [`Self::get_media`].
+    pub media: MediaCache,
     in_flight: HashMap<String, Promise<MimeResult>>,
+    media_in_flight: HashMap<String, Promise<BodyResult>>,
+    failures: HashMap<String, FailureState>,
+    last_evicted: SystemTime,
 }
 
 impl UrlMimes {
-    pub fn new(url_cache: UrlCache) -> Self {
+    pub fn new(url_cache: UrlCache, media: MediaCache) -> Self {
         Self {
             cache: url_cache,
+            media,
             in_flight: Default::default(),
+            media_in_flight: Default::default(),
+            failures: Default::default(),
+            last_evicted: SystemTime::now(),
+        }
+    }
+
+    /// Runs the cache's own periodic save alongside an interval-gated
+    /// [`MediaCache::evict`] pass, so the chunk store doesn't grow
+    /// unbounded. Callers should poll this the same way they poll
+    /// `self.cache.handle_io()` today.
+    pub fn handle_io(&mut self) {
+        self.cache.handle_io();
+
+        if let Ok(elapsed) = SystemTime::now().duration_since(self.last_evicted) {
+            if elapsed >= EVICT_INTERVAL {
+                if let Err(e) = self.media.evict() {
+                    tracing::error!("failed to evict MediaCache: {}", e);
+                }
+                self.last_evicted = SystemTime::now();
+            }
         }
     }
 
     pub fn get(&mut self, url: &str) -> Option<String> {
-        if let Some(mime_type) = self.cache.get_type(url) {
-            Some(mime_type)
-        } else if let Some(promise) = self.in_flight.get_mut(url) {
+        if let Some(entry) = self.cache.get_entry(url) {
+            let is_fresh = entry
+                .fetched_at
+                .elapsed()
+                .map(|elapsed| elapsed < MIME_TTL)
+                .unwrap_or(true);
+
+            if is_fresh {
+                return Some(entry.mime_type);
+            }
+        }
+
+        if let Some(failure) = self.failures.get(url) {
+            if Instant::now() < failure.next_attempt_at {
+                return None;
+            }
+        }
+
+        if let Some(promise) = self.in_flight.get_mut(url) {
             if let Some(mime_result) = promise.ready_mut() {
                 match mime_result {
                     Ok(mime_type) => {
                         let mime_type = mime_type.take();
                         self.cache.set_type(url.to_owned(), mime_type.clone());
                         self.in_flight.remove(url);
+                        self.failures.remove(url);
                         Some(mime_type)
                     }
                     Err(HttpError::HttpFailure) => {
-                        // allow retrying
                         self.in_flight.remove(url);
+                        self.record_failure(url);
                         None
                     }
                     Err(HttpError::MissingHeader) => {
-                        // response was malformed, don't retry
+                        // malformed: back off like an HttpFailure instead of
+                        // refetching on every call, and don't leak the entry
+                        self.in_flight.remove(url);
+                        self.record_failure(url);
                         None
                     }
                 }
@@ -211,4 +344,67 @@ impl UrlMimes {
             None
         }
     }
+
+    /// Fetch and cache the decoded body behind `url` in [`MediaCache`], once
+    /// `get` has resolved a MIME type for it. Shares `self.failures` with
+    /// `get`, so a body fetch that fails backs off the same way.
+    pub fn get_media(&mut self, url: &str) -> Option<(String, Vec<u8>)> {
+        if let Some(cached) = self.media.get(url) {
+            return Some(cached);
+        }
+
+        if let Some(failure) = self.failures.get(url) {
+            if Instant::now() < failure.next_attempt_at {
+                return None;
+            }
+        }
+
+        if let Some(promise) = self.media_in_flight.get_mut(url) {
+            return match promise.ready_mut() {
+                Some(Ok(body)) => {
+                    let body = std::mem::take(body);
+                    let mime_type = self.cache.get_type(url)?;
+                    if let Err(e) = self.media.ingest(url, &mime_type, &body) {
+                        tracing::error!("failed to ingest media for {url}: {e}");
+                    }
+                    self.media_in_flight.remove(url);
+                    self.failures.remove(url);
+                    self.media.get(url)
+                }
+                Some(Err(_)) => {
+                    self.media_in_flight.remove(url);
+                    self.record_failure(url);
+                    None
+                }
+                None => None,
+            };
+        }
+
+        // we can't ingest without a mime type, so don't bother downloading
+        // the body until `get` has resolved one
+        self.cache.get_type(url)?;
+
+        let (sender, promise) = Promise::new();
+        ehttp_get_body(url, sender);
+        self.media_in_flight.insert(url.to_owned(), promise);
+        None
+    }
+
+    /// Double the backoff for `url`, capped at [`MAX_BACKOFF`].
+    fn record_failure(&mut self, url: &str) {
+        let backoff = self
+            .failures
+            .get(url)
+            .map(|f| f.backoff * 2)
+            .unwrap_or(INITIAL_BACKOFF)
+            .min(MAX_BACKOFF);
+
+        self.failures.insert(
+            url.to_owned(),
+            FailureState {
+                next_attempt_at: Instant::now() + backoff,
+                backoff,
+            },
+        );
+    }
 }
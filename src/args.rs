@@ -1,9 +1,15 @@
+use crate::bunker::{BunkerSigner, BunkerTransport};
+use crate::config::Config;
 use crate::timeline::{ColumnKind, ListKind, PubkeySource, Timeline};
 use crate::Error;
 use enostr::{Filter, Keypair, Pubkey, SecretKey};
 use nostrdb::{Ndb, Transaction};
 use tracing::{error, info};
 
+/// How many of the most recent notes already in `Ndb` get fed into a new
+/// `Search` column's index before its first render.
+const SEARCH_SEED_LIMIT: usize = 500;
+
 pub struct Args {
     pub columns: Vec<ArgColumn>,
     pub relays: Vec<String>,
@@ -12,6 +18,7 @@ pub struct Args {
     pub since_optimize: bool,
     pub light: bool,
     pub dbpath: Option<String>,
+    pub bunker: Option<BunkerSigner>,
 }
 
 impl Args {
@@ -24,8 +31,16 @@ impl Args {
             light: false,
             since_optimize: true,
             dbpath: None,
+            bunker: None,
         };
 
+        if let Some(config_path) = find_config_arg(args) {
+            match Config::load(&config_path) {
+                Ok(config) => config.apply_to(&mut res),
+                Err(e) => error!("failed to load config '{}': {}", config_path, e),
+            }
+        }
+
         let mut i = 0;
         let len = args.len();
         while i < len {
@@ -71,6 +86,28 @@ impl Args {
                         arg
                     );
                 }
+            } else if arg == "--bunker" {
+                i += 1;
+                let uri = if let Some(next_arg) = args.get(i) {
+                    next_arg
+                } else {
+                    error!("bunker argument missing?");
+                    continue;
+                };
+
+                match BunkerSigner::parse(uri) {
+                    Ok(signer) => {
+                        // `Args::request_sign` is ready to dispatch through a
+                        // `BunkerTransport`, but nothing in this tree supplies
+                        // one yet, so signing requests to this account go
+                        // nowhere until a relay-backed transport is wired in
+                        info!("loaded --bunker account");
+                        res.keys.push(signer.account_keypair());
+                        res.relays.extend(signer.relays().iter().cloned());
+                        res.bunker = Some(signer);
+                    }
+                    Err(e) => error!("failed to parse --bunker uri: {}", e),
+                }
             } else if arg == "--no-since-optimize" {
                 res.since_optimize = false;
             } else if arg == "--filter" {
@@ -87,6 +124,10 @@ impl Args {
                 } else {
                     error!("failed to parse filter '{}'", filter);
                 }
+            } else if arg == "--config" {
+                // already merged into `res` above, so command-line flags
+                // that follow take precedence over it
+                i += 1;
             } else if arg == "--dbpath" {
                 i += 1;
                 let path = if let Some(next_arg) = args.get(i) {
@@ -128,6 +169,9 @@ impl Args {
                     res.columns.push(ArgColumn::Column(ColumnKind::contact_list(
                         PubkeySource::DeckAuthor,
                     )))
+                } else if let Some(query) = column_name.strip_prefix("search:") {
+                    res.columns
+                        .push(ArgColumn::Column(ColumnKind::Search(query.to_owned())));
                 }
             } else if arg == "--filter-file" || arg == "-f" {
                 i += 1;
@@ -166,6 +210,34 @@ impl Args {
 
         res
     }
+
+    /// Forward a signing request to the `--bunker` account, if one was
+    /// loaded. No-op otherwise.
+    pub fn request_sign(
+        &mut self,
+        transport: &dyn BunkerTransport,
+        request_id: String,
+        unsigned_event_json: String,
+    ) {
+        if let Some(bunker) = self.bunker.as_mut() {
+            bunker.request_sign(transport, request_id, unsigned_event_json);
+        }
+    }
+
+    /// Poll a previously dispatched `--bunker` signing request.
+    pub fn poll_bunker_response(&mut self, request_id: &str) -> Option<Option<String>> {
+        self.bunker.as_mut()?.poll_response(request_id)
+    }
+}
+
+/// Scan for a `--config <path>` flag without disturbing the main parse loop,
+/// so the config file can be loaded and merged before any other flag is
+/// processed.
+fn find_config_arg(args: &[String]) -> Option<String> {
+    args.iter()
+        .position(|a| a == "--config")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
 }
 
 /// A way to define columns from the commandline. Can be column kinds or
@@ -188,6 +260,28 @@ impl ArgColumn {
                 panic!("Not a valid ArgColumn")
             }
 
+            ArgColumn::Column(ColumnKind::Search(query)) => {
+                let mut timeline = Timeline::new(ColumnKind::Search(query), Some(vec![]));
+
+                // seed the column's local index with whatever's already in
+                // `Ndb` so the first render has results instead of an empty
+                // filter (see `Timeline::index_note`'s doc comment for what
+                // this doesn't yet cover)
+                let seed_filter = Filter::new().limit(SEARCH_SEED_LIMIT).build();
+                let txn = Transaction::new(ndb).expect("txn");
+                if let Ok(results) = ndb.query(&txn, vec![seed_filter], SEARCH_SEED_LIMIT as i32) {
+                    for result in &results {
+                        timeline.index_note(
+                            *result.note.id(),
+                            result.note.content(),
+                            result.note.created_at(),
+                        );
+                    }
+                }
+
+                timeline
+            }
+
             ArgColumn::Column(ColumnKind::List(ListKind::Contact(ref pk_src))) => {
                 let pk = match pk_src {
                     PubkeySource::DeckAuthor => {
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use enostr::{Keypair, Pubkey, SecretKey};
+use poll_promise::{Promise, Sender};
+
+/// Publishes a NIP-46 request event and resolves `sender` with the signer's
+/// response once it arrives over the relay pool. Implemented by whatever
+/// owns the relay pool, which isn't in this tree.
+pub trait BunkerTransport {
+    fn publish_request(&self, request_id: &str, envelope: String, sender: Sender<Option<String>>);
+}
+
+/// A remote-signer ("bunker", NIP-46) key source: the local process never
+/// holds the account's secret key, only its public key and a handle for
+/// dispatching signing requests through a caller-supplied [`BunkerTransport`].
+///
+/// NOTE: `enostr::Keypair` currently only distinguishes pubkey-only and
+/// secret-bearing keys. A proper third "remote" variant belongs there so the
+/// rest of the app can treat a bunker-backed key like any other `Keypair`;
+/// that crate isn't part of this tree, so until it grows that variant,
+/// `Args` carries this signer handle alongside a pubkey-only `Keypair` for
+/// the account.
+pub struct BunkerSigner {
+    account_pubkey: Pubkey,
+    /// Encrypts/decrypts the NIP-46 transport envelope; never the account's
+    /// own key. NIP-44 encryption itself isn't wired up yet (see
+    /// `encode_request`), so this is currently unused.
+    transport_key: SecretKey,
+    relays: Vec<String>,
+    pending: HashMap<String, Promise<Option<String>>>,
+}
+
+impl BunkerSigner {
+    /// Parse a `bunker://<signer-pubkey>?relay=<url>&relay=<url>` URI.
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let rest = uri
+            .strip_prefix("bunker://")
+            .ok_or_else(|| format!("not a bunker:// uri: {uri}"))?;
+
+        let (pubkey_part, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let account_pubkey = Pubkey::parse(pubkey_part)
+            .map_err(|_| format!("invalid bunker pubkey: {pubkey_part}"))?;
+
+        let relays = query
+            .split('&')
+            .filter_map(|pair| pair.strip_prefix("relay="))
+            .map(|relay| relay.to_owned())
+            .collect();
+
+        Ok(Self {
+            account_pubkey,
+            transport_key: SecretKey::generate(),
+            relays,
+            pending: HashMap::new(),
+        })
+    }
+
+    pub fn account_keypair(&self) -> Keypair {
+        Keypair::only_pubkey(self.account_pubkey)
+    }
+
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    /// Dispatch a signing request over `transport` and track its response
+    /// under `request_id`, resolved later via [`Self::poll_response`].
+    ///
+    /// A request whose signer never replies (and is never polled again by
+    /// the caller) stays in `pending` for the life of the session; nothing
+    /// in this tree drives retries or timeouts for it yet.
+    pub fn request_sign(
+        &mut self,
+        transport: &dyn BunkerTransport,
+        request_id: String,
+        unsigned_event_json: String,
+    ) {
+        let (sender, promise) = Promise::new();
+        transport.publish_request(&request_id, encode_request(&unsigned_event_json), sender);
+        self.pending.insert(request_id, promise);
+    }
+
+    /// Poll for a completed response, removing it from the pending set once
+    /// it resolves.
+    pub fn poll_response(&mut self, request_id: &str) -> Option<Option<String>> {
+        let response = self.pending.get_mut(request_id)?.ready()?.clone();
+        self.pending.remove(request_id);
+        Some(response)
+    }
+}
+
+/// NIP-46 envelope for a `sign_event` request. Just the raw event JSON for
+/// now - NIP-44 encryption (see `BunkerSigner::transport_key`) isn't wired up
+/// yet, so callers must not treat this as confidential in transit.
+fn encode_request(unsigned_event_json: &str) -> String {
+    unsigned_event_json.to_owned()
+}
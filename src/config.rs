@@ -0,0 +1,107 @@
+use enostr::{Filter, Keypair, Pubkey, SecretKey};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::args::{ArgColumn, Args};
+use crate::timeline::{ColumnKind, PubkeySource};
+
+/// A declarative deck layout loaded via `--config <path.toml>` and merged
+/// into [`Args`] before the command line is parsed, so CLI flags still win.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub relays: Vec<String>,
+
+    #[serde(default)]
+    pub keys: ConfigKeys,
+
+    #[serde(default)]
+    pub columns: Vec<ConfigColumn>,
+
+    pub mobile: Option<bool>,
+    pub light: Option<bool>,
+    pub since_optimize: Option<bool>,
+    pub dbpath: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigKeys {
+    #[serde(default)]
+    pub npub: Vec<String>,
+    #[serde(default)]
+    pub nsec: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigColumn {
+    Contacts {
+        #[serde(default)]
+        npub: Option<String>,
+    },
+    Filter {
+        json: String,
+    },
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    /// Merge this config into `args`: lists are extended, scalars are set
+    /// and then left for the CLI parser to overwrite if it sees the flag.
+    pub fn apply_to(self, args: &mut Args) {
+        args.relays.extend(self.relays);
+
+        for npub in self.keys.npub {
+            match Pubkey::parse(&npub) {
+                Ok(pk) => args.keys.push(Keypair::only_pubkey(pk)),
+                Err(_) => error!("config: failed to parse npub '{}'", npub),
+            }
+        }
+        for nsec in self.keys.nsec {
+            match SecretKey::parse(&nsec) {
+                Ok(sec) => args.keys.push(Keypair::from_secret(sec)),
+                Err(_) => error!("config: failed to parse nsec key"),
+            }
+        }
+
+        for column in self.columns {
+            match column {
+                ConfigColumn::Contacts { npub } => {
+                    let source = match npub {
+                        Some(npub) => match Pubkey::parse(&npub) {
+                            Ok(pk) => PubkeySource::Explicit(pk),
+                            Err(_) => {
+                                error!("config: failed to parse contacts npub '{}'", npub);
+                                continue;
+                            }
+                        },
+                        None => PubkeySource::DeckAuthor,
+                    };
+                    args.columns
+                        .push(ArgColumn::Column(ColumnKind::contact_list(source)));
+                }
+                ConfigColumn::Filter { json } => match Filter::from_json(&json) {
+                    Ok(filter) => args.columns.push(ArgColumn::Generic(vec![filter])),
+                    Err(_) => error!("config: failed to parse filter column '{}'", json),
+                },
+            }
+        }
+
+        if let Some(mobile) = self.mobile {
+            args.is_mobile = Some(mobile);
+        }
+        if let Some(light) = self.light {
+            args.light = light;
+        }
+        if let Some(since_optimize) = self.since_optimize {
+            args.since_optimize = since_optimize;
+        }
+        if self.dbpath.is_some() {
+            args.dbpath = self.dbpath;
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+/// A local inverted index over note content, queried client-side instead of
+/// round-tripping to a relay. Owned by a `Search` column; see
+/// [`crate::timeline::Timeline::index_note`] for how it gets populated.
+#[derive(Default)]
+pub struct SearchIndex {
+    /// term -> posting list of (note id, term frequency in that note)
+    postings: HashMap<String, Vec<(NoteId, u32)>>,
+    recency: HashMap<NoteId, u64>,
+}
+
+pub type NoteId = [u8; 32];
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tokenize and index a note's content. Safe to call again for the same
+    /// note id; its old postings are replaced rather than duplicated.
+    pub fn index_note(&mut self, note_id: NoteId, content: &str, created_at: u64) {
+        self.remove_note(&note_id);
+
+        let mut term_freqs: HashMap<String, u32> = HashMap::new();
+        for term in tokenize(content) {
+            *term_freqs.entry(term).or_insert(0) += 1;
+        }
+
+        for (term, freq) in term_freqs {
+            self.postings
+                .entry(term)
+                .or_default()
+                .push((note_id, freq));
+        }
+
+        self.recency.insert(note_id, created_at);
+    }
+
+    fn remove_note(&mut self, note_id: &NoteId) {
+        for postings in self.postings.values_mut() {
+            postings.retain(|(id, _)| id != note_id);
+        }
+        self.recency.remove(note_id);
+    }
+
+    /// Intersect the posting lists of `query`'s terms, prefix-matching the
+    /// last one, and rank by term frequency plus note recency.
+    pub fn query(&self, query: &str, limit: usize) -> Vec<NoteId> {
+        let terms: Vec<String> = tokenize(query).collect();
+        let Some((last, rest)) = terms.split_last() else {
+            return vec![];
+        };
+
+        let mut candidates: Option<HashMap<NoteId, u32>> = None;
+        for term in rest {
+            let hits = self.exact_matches(term);
+            candidates = Some(intersect(candidates, hits));
+        }
+        let prefix_hits = self.prefix_matches(last);
+        candidates = Some(intersect(candidates, prefix_hits));
+
+        let mut scored: Vec<(NoteId, f64)> = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(note_id, term_freq)| (note_id, self.score(note_id, term_freq)))
+            .collect();
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        scored.into_iter().map(|(note_id, _)| note_id).collect()
+    }
+
+    fn exact_matches(&self, term: &str) -> HashMap<NoteId, u32> {
+        self.postings
+            .get(term)
+            .map(|postings| postings.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn prefix_matches(&self, prefix: &str) -> HashMap<NoteId, u32> {
+        let mut matches = HashMap::new();
+        for (term, postings) in &self.postings {
+            if term.starts_with(prefix) {
+                for (note_id, freq) in postings {
+                    *matches.entry(*note_id).or_insert(0) += freq;
+                }
+            }
+        }
+        matches
+    }
+
+    fn score(&self, note_id: NoteId, term_freq: u32) -> f64 {
+        let recency = self.recency.get(&note_id).copied().unwrap_or(0) as f64;
+        term_freq as f64 + recency / 1_000_000_000.0
+    }
+}
+
+fn intersect(
+    candidates: Option<HashMap<NoteId, u32>>,
+    hits: HashMap<NoteId, u32>,
+) -> HashMap<NoteId, u32> {
+    match candidates {
+        None => hits,
+        Some(candidates) => candidates
+            .into_iter()
+            .filter_map(|(note_id, freq)| {
+                hits.get(&note_id).map(|hit_freq| (note_id, freq + hit_freq))
+            })
+            .collect(),
+    }
+}
+
+/// Lowercase, splitting on runs of non-alphanumeric characters.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+}
@@ -0,0 +1,106 @@
+use enostr::{Filter, Pubkey};
+use nostrdb::Note;
+
+use crate::search::SearchIndex;
+use crate::Error;
+
+/// How a column's author is chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubkeySource {
+    DeckAuthor,
+    Explicit(Pubkey),
+}
+
+impl PubkeySource {
+    pub fn to_owned(&self) -> PubkeySource {
+        self.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ListKind {
+    Contact(PubkeySource),
+}
+
+/// What a [`Timeline`] column shows and how its filter is derived.
+#[derive(Debug, Clone)]
+pub enum ColumnKind {
+    /// Every note the relay pool sees, unfiltered.
+    Universe,
+    /// An arbitrary caller-supplied filter, e.g. from `--filter`.
+    Generic,
+    List(ListKind),
+    /// A client-side search over [`SearchIndex`], keyed by the query string.
+    Search(String),
+}
+
+impl ColumnKind {
+    pub fn contact_list(source: PubkeySource) -> Self {
+        ColumnKind::List(ListKind::Contact(source))
+    }
+}
+
+/// How many of a search column's top matches back its filter.
+const SEARCH_RESULT_LIMIT: usize = 100;
+
+pub struct Timeline {
+    pub kind: ColumnKind,
+    pub filter: Option<Vec<Filter>>,
+    /// Only `Some` for [`ColumnKind::Search`] columns.
+    search_index: Option<SearchIndex>,
+}
+
+impl Timeline {
+    pub fn new(kind: ColumnKind, filter: Option<Vec<Filter>>) -> Self {
+        let search_index = matches!(kind, ColumnKind::Search(_)).then(SearchIndex::new);
+
+        Timeline {
+            kind,
+            filter,
+            search_index,
+        }
+    }
+
+    /// Index a note's content and re-derive this column's filter from the
+    /// current top matches. No-op for every kind but `Search`.
+    ///
+    /// NOTE: today this only runs once, seeding a new `Search` column from
+    /// whatever's already in `Ndb` (see `into_timeline` in `args.rs`).
+    /// Calling it again as new notes arrive - so the column keeps updating
+    /// after creation, per the original request - needs a hook into
+    /// whatever drives the live `Ndb` subscription loop, which isn't part
+    /// of this tree.
+    pub fn index_note(&mut self, note_id: [u8; 32], content: &str, created_at: u64) {
+        let ColumnKind::Search(ref query) = self.kind else {
+            return;
+        };
+        let Some(index) = self.search_index.as_mut() else {
+            return;
+        };
+
+        index.index_note(note_id, content, created_at);
+
+        let matches = index.query(query, SEARCH_RESULT_LIMIT);
+        self.filter = Some(vec![Filter::new().ids(matches).build()]);
+    }
+
+    pub fn contact_list(note: &Note) -> Result<Timeline, Error> {
+        let mut pubkeys: Vec<[u8; 32]> = Vec::new();
+        for tag in note.tags().iter() {
+            let mut fields = tag.into_iter();
+            if fields.next().and_then(|f| f.str()) != Some("p") {
+                continue;
+            }
+            if let Some(pk) = fields.next().and_then(|f| f.id()) {
+                pubkeys.push(*pk);
+            }
+        }
+
+        if pubkeys.is_empty() {
+            return Err(Error::EmptyContactList);
+        }
+
+        let filter = Filter::new().authors(pubkeys).build();
+        Ok(Timeline::new(ColumnKind::Generic, Some(vec![filter])))
+    }
+}